@@ -0,0 +1,106 @@
+//! Attribute-macro companion to `pliron::type_to_trait`, letting dialect
+//! authors register a cast directly on the `impl Trait for Type` block (or
+//! list several traits on the type definition itself) instead of
+//! hand-writing a separate `type_to_trait!(Type, Trait)` call per interface.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, punctuated::Punctuated, Ident, Item, ItemImpl, Path, Token};
+
+/// Register a type as castable to one or more traits, via the same
+/// distributed-slice registration that
+/// [`type_to_trait!`](https://docs.rs/pliron/latest/pliron/macro.type_to_trait.html)
+/// emits.
+///
+/// - On an `impl Trait for Type { .. }` block, with no arguments: registers
+///   `Type` as castable to `Trait`, equivalent to a trailing
+///   `type_to_trait!(Type, Trait);` right after the impl.
+/// - On a type definition, with one or more trait paths as arguments
+///   (`#[cast_to(Trait2, Trait3)]`): registers the type as castable to each
+///   listed trait, one `TRAIT_CASTERS` entry per trait. Use this form when
+///   several interfaces need to be declared for the same type and the
+///   `impl` blocks aren't all in the same place.
+///
+/// `type_to_trait!` keeps working for cases where neither the impl nor the
+/// type definition is available to annotate (e.g. impls for foreign types).
+#[proc_macro_attribute]
+pub fn cast_to(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as Item);
+
+    match item {
+        Item::Impl(item_impl) => cast_to_on_impl(attr, item_impl),
+        other => cast_to_on_type(attr, other),
+    }
+}
+
+fn cast_to_on_impl(attr: TokenStream, item_impl: ItemImpl) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new_spanned(
+            proc_macro2::TokenStream::from(attr),
+            "#[cast_to] takes no arguments when placed on an `impl Trait for Type` block",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let Some((_, trait_path, _)) = &item_impl.trait_ else {
+        return syn::Error::new_spanned(
+            &item_impl,
+            "#[cast_to] on an impl block requires `impl Trait for Type`, not an inherent impl",
+        )
+        .to_compile_error()
+        .into();
+    };
+    let self_ty = &item_impl.self_ty;
+
+    let registration = quote! {
+        ::pliron::type_to_trait!(#self_ty, #trait_path);
+    };
+
+    quote! {
+        #item_impl
+        #registration
+    }
+    .into()
+}
+
+fn cast_to_on_type(attr: TokenStream, item: Item) -> TokenStream {
+    let Some(ident) = item_ident(&item) else {
+        return syn::Error::new_spanned(
+            &item,
+            "#[cast_to(Trait, ..)] must be placed on a struct/enum/union definition, \
+             or on an `impl Trait for Type` block with no arguments",
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    let traits = parse_macro_input!(attr with Punctuated::<Path, Token![,]>::parse_terminated);
+    if traits.is_empty() {
+        return syn::Error::new_spanned(
+            &item,
+            "#[cast_to(Trait, ..)] on a type definition requires at least one trait path",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let registrations = traits.iter().map(|trait_path| {
+        quote! { ::pliron::type_to_trait!(#ident, #trait_path); }
+    });
+
+    quote! {
+        #item
+        #(#registrations)*
+    }
+    .into()
+}
+
+fn item_ident(item: &Item) -> Option<&Ident> {
+    match item {
+        Item::Struct(s) => Some(&s.ident),
+        Item::Enum(e) => Some(&e.ident),
+        Item::Union(u) => Some(&u.ident),
+        _ => None,
+    }
+}
@@ -3,11 +3,13 @@
 //!
 //! A user must specify [type_to_trait](crate::type_to_trait) for a type that implements
 //! a trait and needs to be casted to it, and then use [any_to_trait]
+//! (or [box_to_trait], [rc_to_trait], [arc_to_trait] for owned containers)
 //! to do the actual cast. See their documentation for details and examples.
 
 use std::{
     any::{Any, TypeId},
-    sync::LazyLock,
+    rc::Rc,
+    sync::{Arc, LazyLock},
 };
 
 use downcast_rs::Downcast;
@@ -42,32 +44,214 @@ use rustc_hash::FxHashMap;
 ///
 /// ```
 pub fn any_to_trait<T: ?Sized + 'static>(r: &dyn Any) -> Option<&T> {
+    get_cast_fns::<T>(r.type_id()).and_then(|fns| (fns.cast_ref)(r))
+}
+
+/// Check whether casting `r` to `dyn Trait` via [any_to_trait] would
+/// succeed, without performing the cast or producing a reference. Useful
+/// for branching on "does this value support interface X?" before
+/// committing to a cast, instead of calling [any_to_trait] and discarding
+/// the result just to test membership.
+/// Example:
+/// ```
+/// # use pliron::{type_to_trait, utils::trait_cast::can_cast_to};
+/// # use std::any::Any;
+/// trait Trait {}
+/// struct S;
+/// impl Trait for S {}
+/// type_to_trait!(S, Trait);
+///
+/// let s: &dyn Any = &S;
+/// assert!(can_cast_to::<dyn Trait>(s));
+/// ```
+pub fn can_cast_to<T: ?Sized + 'static>(r: &dyn Any) -> bool {
+    TRAIT_CASTERS_MAP.contains_key(&(r.type_id(), TypeId::of::<T>()))
+}
+
+/// Cast a `&mut dyn Any` object to a `&mut dyn Trait` reference, for any
+/// trait that the contained (in [Any]) type implements, and for which
+/// [type_to_trait](crate::type_to_trait) has been specified. This is the
+/// mutable counterpart of [any_to_trait], letting callers holding only a
+/// `&mut dyn Any` invoke mutating trait methods.
+/// Example:
+/// ```
+/// # use pliron::{type_to_trait, utils::trait_cast::any_to_trait_mut};
+/// # use std::any::Any;
+/// trait Trait {
+///     fn set(&mut self);
+/// }
+/// struct S(bool);
+/// impl Trait for S {
+///     fn set(&mut self) {
+///         self.0 = true;
+///     }
+/// }
+/// type_to_trait!(S, Trait);
+///
+/// let mut s: Box<dyn Any> = Box::new(S(false));
+/// any_to_trait_mut::<dyn Trait>(s.as_mut())
+///     .expect("Expected S to implement Trait")
+///     .set();
+/// ```
+pub fn any_to_trait_mut<T: ?Sized + 'static>(r: &mut dyn Any) -> Option<&mut T> {
+    get_cast_fns::<T>(r.type_id()).and_then(|fns| (fns.cast_mut)(r))
+}
+
+/// Cast a `Box<dyn Any>` to a `Box<dyn Trait>`, for any trait that the boxed
+/// type implements and for which [type_to_trait](crate::type_to_trait) has
+/// been specified. On failure, the original box is handed back in the `Err`
+/// variant so the caller doesn't lose the value.
+/// Example:
+/// ```
+/// # use pliron::{type_to_trait, utils::trait_cast::box_to_trait};
+/// # use std::any::Any;
+/// trait Trait {}
+/// struct S;
+/// impl Trait for S {}
+/// type_to_trait!(S, Trait);
+///
+/// let s: Box<dyn Any> = Box::new(S);
+/// let _t: Box<dyn Trait> = box_to_trait::<dyn Trait>(s).expect("Expected S to implement Trait");
+/// ```
+pub fn box_to_trait<T: ?Sized + 'static>(r: Box<dyn Any>) -> Result<Box<T>, Box<dyn Any>> {
+    match get_cast_fns::<T>((*r).type_id()) {
+        Some(fns) => (fns.cast_box)(r),
+        None => Err(r),
+    }
+}
+
+/// Cast an `Rc<dyn Any>` to an `Rc<dyn Trait>`, analogous to [box_to_trait]
+/// but for reference-counted (non-atomic) ownership.
+pub fn rc_to_trait<T: ?Sized + 'static>(r: Rc<dyn Any>) -> Result<Rc<T>, Rc<dyn Any>> {
+    match get_cast_fns::<T>((*r).type_id()) {
+        Some(fns) => (fns.cast_rc)(r),
+        None => Err(r),
+    }
+}
+
+/// Cast an `Arc<dyn Any + Send + Sync>` to an `Arc<dyn Trait>`, analogous to
+/// [box_to_trait] but for atomically reference-counted ownership.
+pub fn arc_to_trait<T: ?Sized + 'static>(
+    r: Arc<dyn Any + Send + Sync>,
+) -> Result<Arc<T>, Arc<dyn Any + Send + Sync>> {
+    match get_cast_fns::<T>((*r).type_id()) {
+        Some(fns) => (fns.cast_arc)(r),
+        None => Err(r),
+    }
+}
+
+/// Blanket-implemented super-trait giving any sized `'static` type (and any
+/// trait object whose trait declares `CastFrom` as a super-trait) a way to
+/// hand back a `dyn Any` view of itself. [CastTo] builds on this to route `dyn Trait1` -> `dyn Trait2`
+/// casts through [any_to_trait] without the caller upcasting by hand.
+pub trait CastFrom: Any {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn as_any_box(self: Box<Self>) -> Box<dyn Any>;
+    fn as_any_rc(self: Rc<Self>) -> Rc<dyn Any>;
+}
+
+impl<T: Any> CastFrom for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+    fn as_any_box(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+    fn as_any_rc(self: Rc<Self>) -> Rc<dyn Any> {
+        self
+    }
+}
+
+/// Extension trait giving any `dyn Trait1` an ergonomic `.cast::<dyn
+/// Trait2>()` method, for any `Trait2` the concrete type registered via
+/// [type_to_trait](crate::type_to_trait). `Trait1` must declare [CastFrom]
+/// as a super-trait so that its vtable carries the `as_any*` methods.
+///
+/// This is purely additive over [any_to_trait]: it saves the caller from
+/// manually upcasting `dyn Trait1` to `dyn Any` first.
+/// Example:
+/// ```
+/// # use pliron::{type_to_trait, utils::trait_cast::{CastFrom, CastTo}};
+/// trait Trait1: CastFrom {}
+/// trait Trait2 {}
+///
+/// struct S;
+/// impl Trait1 for S {}
+/// impl Trait2 for S {}
+///
+/// type_to_trait!(S, Trait2);
+///
+/// let s1: &dyn Trait1 = &S;
+/// s1.cast::<dyn Trait2>().expect("Expected S to implement Trait2");
+/// assert!(s1.impls::<dyn Trait2>());
+/// ```
+pub trait CastTo {
+    fn cast<T: ?Sized + 'static>(&self) -> Option<&T>;
+    fn cast_mut<T: ?Sized + 'static>(&mut self) -> Option<&mut T>;
+    /// Query whether [cast](CastTo::cast) would succeed for `T`, without
+    /// performing the cast. See [can_cast_to] for the free-function form.
+    fn impls<T: ?Sized + 'static>(&self) -> bool;
+}
+
+impl<S: ?Sized + CastFrom> CastTo for S {
+    fn cast<T: ?Sized + 'static>(&self) -> Option<&T> {
+        any_to_trait::<T>(self.as_any())
+    }
+    fn cast_mut<T: ?Sized + 'static>(&mut self) -> Option<&mut T> {
+        any_to_trait_mut::<T>(self.as_any_mut())
+    }
+    fn impls<T: ?Sized + 'static>(&self) -> bool {
+        can_cast_to::<T>(self.as_any())
+    }
+}
+
+/// Look up the registered [CastFns] bundle for casting objects of `object_id`
+/// into `T`, downcasting it from the type-erased entry in [TRAIT_CASTERS_MAP].
+fn get_cast_fns<T: ?Sized + 'static>(object_id: TypeId) -> Option<&'static CastFns<T>> {
     TRAIT_CASTERS_MAP
-        .get(&(r.type_id(), TypeId::of::<T>()))
-        .and_then(|caster| {
-            if let Some(caster) = (**caster)
-                .as_any()
-                .downcast_ref::<for<'a> fn(&'a (dyn Any + 'static)) -> Option<&'a T>>()
-            {
-                return caster(r);
-            }
-            None
-        })
+        .get(&(object_id, TypeId::of::<T>()))
+        .and_then(|caster| (**caster).as_any().downcast_ref::<CastFns<T>>())
+}
+
+/// The bundle of caster functions registered for a single `(object, trait)`
+/// pair. A single [type_to_trait](crate::type_to_trait) invocation populates
+/// every field here, so all of [any_to_trait], [box_to_trait], [rc_to_trait]
+/// and [arc_to_trait] become available at once.
+#[doc(hidden)]
+pub struct CastFns<T: ?Sized + 'static> {
+    pub cast_ref: for<'a> fn(&'a (dyn Any + 'static)) -> Option<&'a T>,
+    pub cast_mut: for<'a> fn(&'a mut (dyn Any + 'static)) -> Option<&'a mut T>,
+    pub cast_box: fn(Box<dyn Any>) -> Result<Box<T>, Box<dyn Any>>,
+    pub cast_rc: fn(Rc<dyn Any>) -> Result<Rc<T>, Rc<dyn Any>>,
+    pub cast_arc: fn(Arc<dyn Any + Send + Sync>) -> Result<Arc<T>, Arc<dyn Any + Send + Sync>>,
 }
 
+// Derived `Clone` would require `T: Clone`, but every field here is a plain,
+// always-`Copy` function pointer, so implement it by hand.
+impl<T: ?Sized + 'static> Clone for CastFns<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T: ?Sized + 'static> Copy for CastFns<T> {}
+
 pub trait ClonableAny: Any + DynClone + Downcast {}
 dyn_clone::clone_trait_object!(ClonableAny);
 impl<T: Any + DynClone + Downcast> ClonableAny for T {}
 
 #[doc(hidden)]
 #[distributed_slice]
-/// A distributed slice of (type_id of the object, type_id of the trait to cast to, cast function)
+/// A distributed slice of (type_id of the object, type_id of the trait to cast to, cast function bundle)
 pub static TRAIT_CASTERS: [LazyLock<((TypeId, TypeId), Box<dyn ClonableAny + Sync + Send>)>];
 
 #[doc(hidden)]
 /// A map of all the trait casters, indexed by the type_id of the object
 /// and the type_id of the trait to cast to. The map's values are
-/// the cast function pointers. This is used to avoid having to search
+/// the [CastFns] bundle (type-erased). This is used to avoid having to search
 /// through the distributed slice every time we want to cast an object.
 static TRAIT_CASTERS_MAP: LazyLock<
     FxHashMap<(TypeId, TypeId), Box<dyn ClonableAny + Sync + Send>>,
@@ -79,6 +263,13 @@ static TRAIT_CASTERS_MAP: LazyLock<
 });
 
 /// Specify that a type may be casted to a `dyn Trait` object. Use [any_to_trait] for the actual cast.
+///
+/// Dialect authors registering many interfaces per op/type may prefer the
+/// `#[cast_to]` attribute macro (from the `pliron-derive` crate) instead,
+/// which can be placed directly on the `impl Trait for Type` block, or on
+/// the type definition with `#[cast_to(Trait2, Trait3)]` to register several
+/// traits at once. This macro keeps working for cases where the impl isn't
+/// locally available to annotate.
 /// Example:
 /// ```
 /// # use pliron::{type_to_trait, utils::trait_cast::any_to_trait};
@@ -113,21 +304,48 @@ macro_rules! type_to_trait {
                         std::any::TypeId::of::<$ty_name>(),
                         std::any::TypeId::of::<dyn $to_trait_name>(),
                     ),
-                    Box::new(
-                        cast_to_trait
-                            as for<'a> fn(
-                                &'a (dyn std::any::Any + 'static),
-                            )
-                                -> Option<&'a (dyn $to_trait_name + 'static)>,
-                    ),
+                    Box::new($crate::utils::trait_cast::CastFns::<dyn $to_trait_name> {
+                        cast_ref: cast_ref_to_trait,
+                        cast_mut: cast_mut_to_trait,
+                        cast_box: cast_box_to_trait,
+                        cast_rc: cast_rc_to_trait,
+                        cast_arc: cast_arc_to_trait,
+                    }),
                 )
             });
-            fn cast_to_trait<'a>(
+            fn cast_ref_to_trait<'a>(
                 r: &'a (dyn std::any::Any + 'static),
             ) -> Option<&'a (dyn $to_trait_name + 'static)> {
                 r.downcast_ref::<$ty_name>()
                     .map(|s| s as &dyn $to_trait_name)
             }
+            fn cast_mut_to_trait<'a>(
+                r: &'a mut (dyn std::any::Any + 'static),
+            ) -> Option<&'a mut (dyn $to_trait_name + 'static)> {
+                r.downcast_mut::<$ty_name>()
+                    .map(|s| s as &mut dyn $to_trait_name)
+            }
+            fn cast_box_to_trait(
+                r: Box<dyn std::any::Any>,
+            ) -> Result<Box<dyn $to_trait_name>, Box<dyn std::any::Any>> {
+                r.downcast::<$ty_name>()
+                    .map(|s| s as Box<dyn $to_trait_name>)
+            }
+            fn cast_rc_to_trait(
+                r: std::rc::Rc<dyn std::any::Any>,
+            ) -> Result<std::rc::Rc<dyn $to_trait_name>, std::rc::Rc<dyn std::any::Any>> {
+                r.downcast::<$ty_name>()
+                    .map(|s| s as std::rc::Rc<dyn $to_trait_name>)
+            }
+            fn cast_arc_to_trait(
+                r: std::sync::Arc<dyn std::any::Any + Send + Sync>,
+            ) -> Result<
+                std::sync::Arc<dyn $to_trait_name>,
+                std::sync::Arc<dyn std::any::Any + Send + Sync>,
+            > {
+                r.downcast::<$ty_name>()
+                    .map(|s| s as std::sync::Arc<dyn $to_trait_name>)
+            }
         };
     };
 }